@@ -10,10 +10,8 @@ use secp256k1::{
 };
 use sha3::{Digest, Keccak256};
 use std::{
-    collections::{
-        hash_map::Entry::{Occupied, Vacant},
-        BTreeMap, HashMap, VecDeque,
-    },
+    cmp::Ordering,
+    collections::{hash_map::Entry::Occupied, BTreeMap, BinaryHeap, HashMap, VecDeque},
     convert::TryFrom,
     sync::Arc,
 };
@@ -28,7 +26,7 @@ struct RichTransaction {
 
 impl RichTransaction {
     fn cost(&self) -> U256 {
-        self.inner.gas_limit * self.inner.gas_price
+        self.inner.gas_limit * self.inner.gas_price + self.inner.value
     }
 }
 
@@ -115,21 +113,63 @@ pub enum ImportError {
     FeeTooLow,
     #[error("not enough balance to pay for gas")]
     InsufficientBalance,
+    #[error("too many transactions in the pool")]
+    TooManyForSender,
     #[error("other: {0}")]
     Other(anyhow::Error),
 }
 
+/// Orders pending transactions for block production. Higher score is better.
+pub trait Scoring: Send + Sync {
+    fn score(&self, tx: &Transaction) -> U256;
+}
+
+/// Default [`Scoring`] implementation, ranking transactions by gas price alone.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct GasPriceScoring;
+
+impl Scoring for GasPriceScoring {
+    fn score(&self, tx: &Transaction) -> U256 {
+        tx.gas_price
+    }
+}
+
+/// Maximum number of nonce-gap transactions a single sender may have parked
+/// in [`AccountPool::future`] at once.
+const MAX_FUTURE_PER_SENDER: usize = 64;
+
 struct AccountPool {
     nonce_offset: u64,
     balance: U256,
     txs: VecDeque<Arc<RichTransaction>>,
+    /// Transactions whose nonce is beyond the ready prefix, keyed by absolute nonce.
+    /// Still indexed in [`Pool::by_hash`]; promoted into `txs` once the gap closes.
+    future: BTreeMap<u64, Arc<RichTransaction>>,
 }
 
+/// Default for [`Pool::min_replacement_bump_percent`], matching OpenEthereum's
+/// `should_replace` gas price bump requirement.
+const DEFAULT_MIN_REPLACEMENT_BUMP_PERCENT: u32 = 10;
+
+/// Default for [`Pool::max_transactions`].
+const DEFAULT_MAX_TRANSACTIONS: usize = 8192;
+
+/// Default for [`Pool::max_per_sender`].
+const DEFAULT_MAX_PER_SENDER: usize = 64;
+
 pub struct Pool<DP> {
     block: u64,
     data_provider: DP,
     by_hash: HashMap<H256, Arc<RichTransaction>>,
     by_sender: HashMap<Address, AccountPool>,
+    min_replacement_bump_percent: u32,
+    max_transactions: usize,
+    max_per_sender: usize,
+    min_gas_price: U256,
+    /// Expected EIP-155 chain ID. `None` disables chain ID checking entirely.
+    chain_id: Option<u64>,
+    /// Whether pre-EIP-155 (chain-unprotected) transactions are admitted.
+    allow_unprotected: bool,
 }
 
 impl<DP> Pool<DP> {
@@ -139,7 +179,165 @@ impl<DP> Pool<DP> {
             data_provider,
             by_hash: Default::default(),
             by_sender: Default::default(),
+            min_replacement_bump_percent: DEFAULT_MIN_REPLACEMENT_BUMP_PERCENT,
+            max_transactions: DEFAULT_MAX_TRANSACTIONS,
+            max_per_sender: DEFAULT_MAX_PER_SENDER,
+            min_gas_price: U256::zero(),
+            chain_id: None,
+            allow_unprotected: false,
+        }
+    }
+
+    /// Set the minimum percentage by which a replacement transaction's gas
+    /// price must exceed the one it replaces.
+    pub fn set_min_replacement_bump_percent(&mut self, percent: u32) {
+        self.min_replacement_bump_percent = percent;
+    }
+
+    /// Set the maximum number of ready transactions held across all senders.
+    pub fn set_max_transactions(&mut self, max_transactions: usize) {
+        self.max_transactions = max_transactions;
+    }
+
+    /// Set the maximum number of ready transactions held for a single sender.
+    pub fn set_max_per_sender(&mut self, max_per_sender: usize) {
+        self.max_per_sender = max_per_sender;
+    }
+
+    /// Set the minimum gas price required from non-replacement transactions,
+    /// e.g. to be raised by an operator when the pool is near capacity. Raising
+    /// the floor sweeps existing ready and future transactions priced below it,
+    /// along with their nonce-dependents.
+    pub fn set_min_gas_price(&mut self, min_gas_price: U256) {
+        let raised = min_gas_price > self.min_gas_price;
+        self.min_gas_price = min_gas_price;
+
+        if !raised {
+            return;
+        }
+
+        for account_pool in self.by_sender.values_mut() {
+            let mut cutoff = u64::MAX;
+            if let Some(idx) = account_pool
+                .txs
+                .iter()
+                .position(|tx| tx.inner.gas_price < min_gas_price)
+            {
+                cutoff = account_pool.nonce_offset + idx as u64;
+                for tx in account_pool.txs.split_off(idx) {
+                    self.by_hash.remove(&tx.hash);
+                }
+            }
+
+            // Future entries from the first swept nonce on are its dependents.
+            let stale_nonces: Vec<u64> = account_pool
+                .future
+                .iter()
+                .skip_while(|&(&nonce, tx)| nonce < cutoff && tx.inner.gas_price >= min_gas_price)
+                .map(|(&nonce, _)| nonce)
+                .collect();
+
+            for nonce in stale_nonces {
+                if let Some(tx) = account_pool.future.remove(&nonce) {
+                    self.by_hash.remove(&tx.hash);
+                }
+            }
+        }
+    }
+
+    /// Set the expected EIP-155 chain ID. Transactions signed for a
+    /// different chain are rejected. Pass `None` to disable the check.
+    pub fn set_chain_id(&mut self, chain_id: Option<u64>) {
+        self.chain_id = chain_id;
+    }
+
+    /// Set whether pre-EIP-155 (chain-unprotected) transactions are
+    /// admitted when a `chain_id` is configured.
+    pub fn set_allow_unprotected(&mut self, allow_unprotected: bool) {
+        self.allow_unprotected = allow_unprotected;
+    }
+}
+
+/// Whether `new_price` bumps `old_price` by at least `min_bump_percent`.
+fn meets_replacement_bump(min_bump_percent: u32, old_price: U256, new_price: U256) -> bool {
+    let min_price = old_price + old_price * U256::from(min_bump_percent) / U256::from(100);
+    new_price >= min_price
+}
+
+struct PendingHead {
+    score: U256,
+    sender: Address,
+    index: usize,
+}
+
+impl PartialEq for PendingHead {
+    fn eq(&self, other: &Self) -> bool {
+        self.score == other.score && self.sender == other.sender
+    }
+}
+
+impl Eq for PendingHead {}
+
+impl Ord for PendingHead {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.score.cmp(&other.score).then_with(|| self.sender.cmp(&other.sender))
+    }
+}
+
+impl PartialOrd for PendingHead {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+/// Iterator over pool transactions in descending score order, merging each
+/// sender's ready transactions (the contiguous prefix of [`AccountPool::txs`]
+/// starting at `nonce_offset`) into a single globally-ordered stream.
+pub struct Pending<'p, DP, S> {
+    pool: &'p Pool<DP>,
+    scoring: S,
+    heap: BinaryHeap<PendingHead>,
+    gas_limit: U256,
+    gas_used: U256,
+}
+
+impl<'p, DP, S: Scoring> Iterator for Pending<'p, DP, S> {
+    type Item = &'p Transaction;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        while let Some(head) = self.heap.pop() {
+            let account_pool = match self.pool.by_sender.get(&head.sender) {
+                Some(account_pool) => account_pool,
+                None => continue,
+            };
+            let tx = match account_pool.txs.get(head.index) {
+                Some(tx) => tx,
+                None => continue,
+            };
+
+            // If this transaction doesn't fit, its sender's later
+            // transactions can't either (nonce order must be preserved), so
+            // drop the rest of this sender's queue. Other senders' cheaper
+            // transactions may still fit, so keep going rather than
+            // stopping the whole stream.
+            let gas_used = match self.gas_used.checked_add(tx.inner.gas_limit) {
+                Some(gas_used) if gas_used <= self.gas_limit => gas_used,
+                _ => continue,
+            };
+            self.gas_used = gas_used;
+
+            if let Some(next_tx) = account_pool.txs.get(head.index + 1) {
+                self.heap.push(PendingHead {
+                    score: self.scoring.score(&next_tx.inner),
+                    sender: head.sender,
+                    index: head.index + 1,
+                });
+            }
+
+            return Some(&tx.inner);
         }
+
+        None
     }
 }
 
@@ -148,99 +346,265 @@ impl<DP: AccountInfoProvider> Pool<DP> {
         self.by_hash.get(&hash).map(|tx| &tx.inner)
     }
 
+    /// Iterate pending (ready) transactions ordered by descending gas price,
+    /// skipping any transaction that would push cumulative gas past
+    /// `gas_limit` (and, since nonce order must be preserved, the rest of
+    /// its sender's queue with it) in favor of the next-best transaction
+    /// from another sender.
+    pub fn pending(&self, gas_limit: U256) -> Pending<'_, DP, GasPriceScoring> {
+        self.pending_with_scoring(GasPriceScoring, gas_limit)
+    }
+
+    /// Like [`Pool::pending`], but with a caller-supplied [`Scoring`] implementation.
+    pub fn pending_with_scoring<S: Scoring>(&self, scoring: S, gas_limit: U256) -> Pending<'_, DP, S> {
+        let mut heap = BinaryHeap::with_capacity(self.by_sender.len());
+        for (&sender, account_pool) in &self.by_sender {
+            if let Some(tx) = account_pool.txs.front() {
+                heap.push(PendingHead {
+                    score: scoring.score(&tx.inner),
+                    sender,
+                    index: 0,
+                });
+            }
+        }
+
+        Pending {
+            pool: self,
+            scoring,
+            heap,
+            gas_limit,
+            gas_used: U256::zero(),
+        }
+    }
+
     pub async fn import(&mut self, tx: Transaction) -> Result<bool, ImportError> {
-        let mut tx =
-            Arc::new(RichTransaction::try_from(tx).map_err(ImportError::InvalidTransaction)?);
+        let tx = Arc::new(RichTransaction::try_from(tx).map_err(ImportError::InvalidTransaction)?);
 
         if tx.inner.nonce > U256::from(u64::MAX) {
             return Err(ImportError::InvalidTransaction(anyhow!("nonce too large")));
         }
 
-        match self.by_hash.entry(tx.hash) {
-            Occupied(_) => {
-                // Tx already there.
-                Ok(false)
+        if let Some(expected_chain_id) = self.chain_id {
+            match tx.inner.signature.chain_id() {
+                Some(chain_id) if chain_id == expected_chain_id => {}
+                Some(_) => {
+                    return Err(ImportError::InvalidTransaction(anyhow!("wrong chain id")));
+                }
+                None if self.allow_unprotected => {}
+                None => {
+                    return Err(ImportError::InvalidTransaction(anyhow!("wrong chain id")));
+                }
             }
-            Vacant(tx_by_hash_entry) => {
-                // This is a new transaction.
-                let account_pool = match self.by_sender.entry(tx.sender) {
-                    Occupied(occupied) => occupied.into_mut(),
-                    Vacant(entry) => {
-                        // This is a new sender, let's get its state.
-                        let info = self
-                            .data_provider
-                            .get_account_info(self.block, tx.sender)
-                            .await
-                            .map_err(ImportError::InvalidSender)?
-                            .ok_or_else(|| {
-                                ImportError::InvalidSender(anyhow!("sender account does not exist"))
-                            })?;
-
-                        entry.insert(AccountPool {
-                            nonce_offset: info.nonce,
-                            balance: info.balance,
-                            txs: Default::default(),
-                        })
-                    }
-                };
-
-                if let Some(offset) = tx
-                    .inner
-                    .nonce
-                    .as_u64()
-                    .checked_sub(account_pool.nonce_offset)
-                {
-                    // This transaction's nonce is account nonce or greater.
-                    if offset <= account_pool.txs.len() as u64 {
-                        // This transaction is between existing txs in the pool, or right the next one.
-
-                        // Compute balance after executing all txs before it.
-                        let mut cumulative_balance = account_pool
-                            .txs
-                            .iter()
-                            .take(offset as usize)
-                            .fold(account_pool.balance, |balance, tx| balance - tx.cost());
-
-                        // If this is a replacement transaction, pick between this and old.
-                        if let Some(pooled_tx) = account_pool.txs.get_mut(offset as usize) {
-                            if pooled_tx.inner.gas_price >= tx.inner.gas_price {
-                                return Err(ImportError::FeeTooLow);
-                            }
+        }
 
-                            if cumulative_balance.checked_sub(tx.cost()).is_none() {
-                                return Err(ImportError::InsufficientBalance);
-                            }
+        if self.by_hash.contains_key(&tx.hash) {
+            // Tx already there.
+            return Ok(false);
+        }
 
-                            std::mem::swap(&mut tx, pooled_tx);
-                        }
+        if !self.by_sender.contains_key(&tx.sender) {
+            // This is a new sender, let's get its state.
+            let info = self
+                .data_provider
+                .get_account_info(self.block, tx.sender)
+                .await
+                .map_err(ImportError::InvalidSender)?
+                .ok_or_else(|| ImportError::InvalidSender(anyhow!("sender account does not exist")))?;
 
-                        let mut dropping = VecDeque::new();
+            self.by_sender.insert(
+                tx.sender,
+                AccountPool {
+                    nonce_offset: info.nonce,
+                    balance: info.balance,
+                    txs: Default::default(),
+                    future: Default::default(),
+                },
+            );
+        }
 
-                        // Compute the balance after executing remaining transactions. Select for removal those for which we do not have enough balance.
-                        for (i, tx) in account_pool.txs.iter().enumerate().skip(offset as usize) {
-                            if let Some(balance) = cumulative_balance.checked_sub(tx.cost()) {
-                                cumulative_balance = balance;
-                            } else {
-                                dropping = account_pool.txs.split_off(i);
-                                break;
-                            }
-                        }
+        let nonce = tx.inner.nonce.as_u64();
+        let (nonce_offset, ready_len) = {
+            let account_pool = &self.by_sender[&tx.sender];
+            (account_pool.nonce_offset, account_pool.txs.len() as u64)
+        };
+
+        let offset = match nonce.checked_sub(nonce_offset) {
+            Some(offset) => offset,
+            // Nonce lower than account, meaning it's stale.
+            None => return Err(ImportError::StaleTransaction),
+        };
 
-                        tx_by_hash_entry.insert(tx);
+        if offset == ready_len {
+            // This transaction would grow the sender's ready queue: it is not a
+            // replacement, so it must clear the price floor before anything else.
+            if tx.inner.gas_price < self.min_gas_price {
+                return Err(ImportError::FeeTooLow);
+            }
 
-                        for item in dropping {
-                            self.by_hash.remove(&item.hash);
-                        }
+            // Enforce per-sender capacity before committing anything. The
+            // incoming transaction always lands immediately after this
+            // sender's entire current ready queue, so every ready
+            // transaction already held for it is one of its nonce
+            // predecessors: evicting any of them would not free a usable
+            // slot, it would just strand the incoming transaction as an
+            // unpromotable nonce gap. There is no sound per-sender eviction
+            // to perform, so decline outright.
+            if ready_len >= self.max_per_sender as u64 {
+                return Err(ImportError::TooManyForSender);
+            }
 
-                        Ok(true)
-                    } else {
-                        Err(ImportError::NonceGap)
-                    }
+            let total_ready: usize = self.by_sender.values().map(|p| p.txs.len()).sum();
+            if total_ready >= self.max_transactions {
+                self.make_room_globally(tx.sender, tx.inner.gas_price)?;
+            }
+        }
+
+        let account_pool = self
+            .by_sender
+            .get_mut(&tx.sender)
+            .expect("account pool created above");
+
+        if offset <= account_pool.txs.len() as u64 {
+            // This transaction is between existing txs in the pool, or right the next one.
+
+            // Compute balance after executing all txs before it.
+            let mut cumulative_balance = account_pool
+                .txs
+                .iter()
+                .take(offset as usize)
+                .fold(account_pool.balance, |balance, tx| balance - tx.cost());
+
+            // If this is a replacement transaction, pick between this and old.
+            if let Some(pooled_tx) = account_pool.txs.get_mut(offset as usize) {
+                if !meets_replacement_bump(
+                    self.min_replacement_bump_percent,
+                    pooled_tx.inner.gas_price,
+                    tx.inner.gas_price,
+                ) {
+                    return Err(ImportError::FeeTooLow);
+                }
+
+                if cumulative_balance.checked_sub(tx.cost()).is_none() {
+                    return Err(ImportError::InsufficientBalance);
+                }
+
+                let replaced = std::mem::replace(pooled_tx, tx.clone());
+                self.by_hash.remove(&replaced.hash);
+            } else {
+                account_pool.txs.push_back(tx.clone());
+            }
+
+            let mut dropping = VecDeque::new();
+
+            // Compute the balance after executing remaining transactions. Select for removal those for which we do not have enough balance.
+            for (i, tx) in account_pool.txs.iter().enumerate().skip(offset as usize) {
+                if let Some(balance) = cumulative_balance.checked_sub(tx.cost()) {
+                    cumulative_balance = balance;
                 } else {
-                    // Nonce lower than account, meaning it's stale.
-                    Err(ImportError::StaleTransaction)
+                    dropping = account_pool.txs.split_off(i);
+                    break;
                 }
             }
+
+            self.by_hash.insert(tx.hash, tx);
+
+            for item in dropping {
+                self.by_hash.remove(&item.hash);
+            }
+
+            Self::promote_future(account_pool);
+
+            Ok(true)
+        } else {
+            // Nonce gap: park until the earlier transactions arrive.
+            if let Some(existing) = account_pool.future.get(&nonce) {
+                if !meets_replacement_bump(
+                    self.min_replacement_bump_percent,
+                    existing.inner.gas_price,
+                    tx.inner.gas_price,
+                ) {
+                    return Err(ImportError::FeeTooLow);
+                }
+            } else {
+                if tx.inner.gas_price < self.min_gas_price {
+                    return Err(ImportError::FeeTooLow);
+                }
+
+                if account_pool.future.len() >= MAX_FUTURE_PER_SENDER {
+                    if let Some((_, evicted)) = account_pool.future.pop_last() {
+                        self.by_hash.remove(&evicted.hash);
+                    }
+                }
+            }
+
+            if let Some(replaced) = account_pool.future.insert(nonce, tx.clone()) {
+                self.by_hash.remove(&replaced.hash);
+            }
+
+            self.by_hash.insert(tx.hash, tx);
+
+            Ok(true)
+        }
+    }
+
+    /// Evict the pool's single worst-scoring ready transaction belonging to
+    /// a sender other than `sender` — and everything after it in its
+    /// account, to keep nonces contiguous — to make room for a new one,
+    /// provided the new transaction outscores it.
+    ///
+    /// `sender`'s own ready transactions are never eviction candidates:
+    /// evicting one of them would only strand the incoming transaction as an
+    /// unpromotable nonce gap instead of freeing real capacity (the same
+    /// reasoning that rules out any per-sender-capacity eviction at all).
+    fn make_room_globally(&mut self, sender: Address, new_price: U256) -> Result<(), ImportError> {
+        let worst = self
+            .by_sender
+            .iter()
+            .filter(|&(&other, _)| other != sender)
+            .flat_map(|(&other, pool)| {
+                pool.txs
+                    .iter()
+                    .enumerate()
+                    .map(move |(i, t)| (other, i, t.inner.gas_price))
+            })
+            .min_by(|&(_, ia, pa), &(_, ib, pb)| pa.cmp(&pb).then(ib.cmp(&ia)));
+
+        match worst {
+            Some((other, idx, price)) if new_price > price => {
+                let account_pool = self
+                    .by_sender
+                    .get_mut(&other)
+                    .expect("sender just observed");
+                let evicted = account_pool.txs.split_off(idx);
+                for item in evicted {
+                    self.by_hash.remove(&item.hash);
+                }
+                Ok(())
+            }
+            _ => Err(ImportError::TooManyForSender),
+        }
+    }
+
+    /// Promote contiguous, balance-covered transactions from `future` into the
+    /// ready `txs` queue after an append has advanced `nonce_offset + txs.len()`.
+    fn promote_future(account_pool: &mut AccountPool) {
+        let mut cumulative_balance = account_pool
+            .txs
+            .iter()
+            .fold(account_pool.balance, |balance, tx| balance - tx.cost());
+
+        while let Some(next_tx) = account_pool
+            .future
+            .get(&(account_pool.nonce_offset + account_pool.txs.len() as u64))
+        {
+            match cumulative_balance.checked_sub(next_tx.cost()) {
+                Some(balance) => cumulative_balance = balance,
+                None => break,
+            }
+
+            let nonce = account_pool.nonce_offset + account_pool.txs.len() as u64;
+            let tx = account_pool.future.remove(&nonce).expect("just matched above");
+            account_pool.txs.push_back(tx);
         }
     }
 
@@ -302,15 +666,32 @@ impl<DP: AccountInfoProvider> Pool<DP> {
                             break;
                         }
                     }
+
+                    if !validation_error {
+                        // Nonce offset advanced: drop now-stale future txs and promote any
+                        // that became contiguous.
+                        let stale: Vec<u64> =
+                            pool.future.range(..pool.nonce_offset).map(|(&n, _)| n).collect();
+                        for nonce in stale {
+                            if let Some(tx) = pool.future.remove(&nonce) {
+                                self.by_hash.remove(&tx.hash);
+                            }
+                        }
+                        Self::promote_future(pool);
+                    }
                 } else {
                     validation_error = true;
                 }
 
                 if validation_error {
                     // We will drop all transactions from this sender now
-                    for tx in entry.remove().txs {
+                    let pool = entry.remove();
+                    for tx in pool.txs {
                         assert!(self.by_hash.remove(&tx.hash).is_some());
                     }
+                    for tx in pool.future.into_values() {
+                        self.by_hash.remove(&tx.hash);
+                    }
                 }
             }
         }
@@ -346,6 +727,9 @@ impl<DP: AccountInfoProvider> Pool<DP> {
                 for tx in pool.txs {
                     assert!(self.by_hash.remove(&tx.hash).is_some());
                 }
+                for tx in pool.future.into_values() {
+                    self.by_hash.remove(&tx.hash);
+                }
             }
         }
 
@@ -364,3 +748,379 @@ impl<DP: AccountInfoProvider> Pool<DP> {
         self.block = block;
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ethereum::{TransactionAction, TransactionSignature};
+    use secp256k1::SecretKey;
+
+    fn secret_key(byte: u8) -> SecretKey {
+        SecretKey::from_slice(&[byte; 32]).unwrap()
+    }
+
+    fn signed_tx(secret: &SecretKey, nonce: u64, gas_price: u64, value: u64) -> Transaction {
+        signed_tx_with_gas_limit(secret, nonce, gas_price, value, 21_000)
+    }
+
+    fn signed_tx_with_gas_limit(
+        secret: &SecretKey,
+        nonce: u64,
+        gas_price: u64,
+        value: u64,
+        gas_limit: u64,
+    ) -> Transaction {
+        signed_tx_inner(secret, nonce, gas_price, value, gas_limit, None)
+    }
+
+    /// Like [`signed_tx`], but signed as an EIP-155-protected transaction for
+    /// `chain_id`.
+    fn signed_tx_eip155(
+        secret: &SecretKey,
+        nonce: u64,
+        gas_price: u64,
+        value: u64,
+        chain_id: u64,
+    ) -> Transaction {
+        signed_tx_inner(secret, nonce, gas_price, value, 21_000, Some(chain_id))
+    }
+
+    fn signed_tx_inner(
+        secret: &SecretKey,
+        nonce: u64,
+        gas_price: u64,
+        value: u64,
+        gas_limit: u64,
+        chain_id: Option<u64>,
+    ) -> Transaction {
+        // The placeholder signature's `v` carries the chain ID (if any) so that
+        // the signed message below includes it, as EIP-155 requires.
+        let placeholder_v = match chain_id {
+            Some(chain_id) => 35 + chain_id * 2,
+            None => 27,
+        };
+
+        let mut tx = Transaction {
+            nonce: U256::from(nonce),
+            gas_price: U256::from(gas_price),
+            gas_limit: U256::from(gas_limit),
+            action: TransactionAction::Call(Address::zero()),
+            value: U256::from(value),
+            input: Vec::new(),
+            signature: TransactionSignature::new(
+                placeholder_v,
+                H256::from_low_u64_be(1),
+                H256::from_low_u64_be(1),
+            )
+            .unwrap(),
+        };
+
+        let hash = ethereum::TransactionMessage::from(tx.clone()).hash();
+        let (recovery_id, sig) = SECP256K1
+            .sign_recoverable(&Message::from_slice(hash.as_bytes()).unwrap(), secret)
+            .serialize_compact();
+
+        let v = match chain_id {
+            Some(chain_id) => recovery_id.to_i32() as u64 + 35 + chain_id * 2,
+            None => recovery_id.to_i32() as u64 + 27,
+        };
+
+        tx.signature = TransactionSignature::new(
+            v,
+            H256::from_slice(&sig[..32]),
+            H256::from_slice(&sig[32..]),
+        )
+        .unwrap();
+
+        tx
+    }
+
+    fn sender_of(tx: &Transaction) -> Address {
+        RichTransaction::try_from(tx.clone()).unwrap().sender
+    }
+
+    fn pool_with_balance(
+        sender: Address,
+        balance: u64,
+    ) -> Pool<HashMap<u64, HashMap<Address, AccountInfo>>> {
+        pool_with_balances(&[(sender, balance)])
+    }
+
+    fn pool_with_balances(
+        senders: &[(Address, u64)],
+    ) -> Pool<HashMap<u64, HashMap<Address, AccountInfo>>> {
+        let accounts = senders
+            .iter()
+            .map(|&(sender, balance)| {
+                (
+                    sender,
+                    AccountInfo {
+                        balance: U256::from(balance),
+                        nonce: 0,
+                    },
+                )
+            })
+            .collect();
+
+        let mut data_provider = HashMap::new();
+        data_provider.insert(0, accounts);
+
+        Pool::new(0, data_provider)
+    }
+
+    #[tokio::test]
+    async fn value_transfer_chain_truncated_when_balance_runs_out() {
+        let secret = secret_key(1);
+        let tx0 = signed_tx(&secret, 0, 1, 40);
+        let tx1 = signed_tx(&secret, 1, 1, 40);
+        let tx2 = signed_tx(&secret, 2, 1, 40);
+        let sender = sender_of(&tx0);
+
+        // Gas cost per tx is 21_000, so only the first two txs (21_040 each)
+        // fit in a balance that leaves no room for a third.
+        let mut pool = pool_with_balance(sender, 21_040 * 2);
+
+        let hash0 = RichTransaction::try_from(tx0.clone()).unwrap().hash;
+        let hash1 = RichTransaction::try_from(tx1.clone()).unwrap().hash;
+        let hash2 = RichTransaction::try_from(tx2.clone()).unwrap().hash;
+
+        assert!(pool.import(tx0).await.unwrap());
+        assert!(pool.import(tx1).await.unwrap());
+        assert!(pool.import(tx2).await.unwrap());
+
+        assert!(pool.get(hash0).is_some());
+        assert!(pool.get(hash1).is_some());
+        assert!(pool.get(hash2).is_none());
+    }
+
+    #[tokio::test]
+    async fn replacement_value_pushes_later_tx_over_balance() {
+        let secret = secret_key(2);
+        let tx0 = signed_tx(&secret, 0, 1, 10);
+        let tx1 = signed_tx(&secret, 1, 1, 10);
+        let sender = sender_of(&tx0);
+
+        // Balance covers both original txs (21_000 gas + 10 value each)
+        // exactly, with nothing left over.
+        let mut pool = pool_with_balance(sender, (21_000 + 10) * 2);
+
+        let hash0 = RichTransaction::try_from(tx0.clone()).unwrap().hash;
+        let hash1 = RichTransaction::try_from(tx1.clone()).unwrap().hash;
+
+        assert!(pool.import(tx0).await.unwrap());
+        assert!(pool.import(tx1).await.unwrap());
+        assert!(pool.get(hash0).is_some());
+        assert!(pool.get(hash1).is_some());
+
+        // Replace tx0 with a same-nonce transaction (gas price doubled, to
+        // clear the replacement bump) whose higher value eats into the
+        // balance tx1 depended on.
+        let tx0_replacement = signed_tx(&secret, 0, 2, 11);
+        let hash0_replacement = RichTransaction::try_from(tx0_replacement.clone())
+            .unwrap()
+            .hash;
+
+        assert!(pool.import(tx0_replacement).await.unwrap());
+
+        assert!(pool.get(hash0_replacement).is_some());
+        assert!(pool.get(hash0).is_none());
+        assert!(pool.get(hash1).is_none());
+    }
+
+    #[tokio::test]
+    async fn per_sender_cap_rejects_append_instead_of_stranding_it() {
+        let secret = secret_key(3);
+        let tx0 = signed_tx(&secret, 0, 100, 0);
+        let tx1 = signed_tx(&secret, 1, 1, 0);
+        let tx2 = signed_tx(&secret, 2, 50, 0);
+        let sender = sender_of(&tx0);
+
+        // Balance must cover tx0's cost at its gas price of 100, not just a
+        // flat multiple of gas_limit.
+        let mut pool = pool_with_balance(sender, 21_000 * 150);
+        pool.set_max_per_sender(2);
+
+        assert!(pool.import(tx0).await.unwrap());
+        assert!(pool.import(tx1).await.unwrap());
+
+        // The pool is already at the per-sender cap. Evicting either ready
+        // transaction to admit tx2 would strand tx2 forever (it depends on
+        // both as nonce predecessors), so the import must be rejected
+        // outright rather than silently orphaning it in the future queue.
+        assert!(matches!(
+            pool.import(tx2).await,
+            Err(ImportError::TooManyForSender)
+        ));
+
+        let pending: Vec<U256> = pool.pending(U256::from(1_000_000)).map(|tx| tx.nonce).collect();
+        assert_eq!(pending, vec![U256::from(0), U256::from(1)]);
+    }
+
+    #[tokio::test]
+    async fn global_cap_evicts_other_senders_worst_tx() {
+        let low_secret = secret_key(4);
+        let high_secret = secret_key(5);
+        let low_tx = signed_tx(&low_secret, 0, 1, 0);
+        let high_tx = signed_tx(&high_secret, 0, 5, 0);
+        let low_sender = sender_of(&low_tx);
+        let high_sender = sender_of(&high_tx);
+
+        let mut pool =
+            pool_with_balances(&[(low_sender, 21_000 * 10), (high_sender, 21_000 * 10)]);
+        pool.set_max_transactions(1);
+
+        let low_hash = RichTransaction::try_from(low_tx.clone()).unwrap().hash;
+        let high_hash = RichTransaction::try_from(high_tx.clone()).unwrap().hash;
+
+        assert!(pool.import(low_tx).await.unwrap());
+        assert!(pool.import(high_tx).await.unwrap());
+
+        // The global cap was hit by a different sender's pricier tx, so the
+        // cheaper one is evicted (not the importing sender's own, which
+        // could never safely be evicted for its own append).
+        assert!(pool.get(low_hash).is_none());
+        assert!(pool.get(high_hash).is_some());
+    }
+
+    #[tokio::test]
+    async fn pending_skips_an_oversized_sender_instead_of_stopping() {
+        let big_secret = secret_key(6);
+        let small_secret = secret_key(7);
+        // Highest score, but alone too large for the block; must not
+        // truncate the whole stream.
+        let big_tx = signed_tx_with_gas_limit(&big_secret, 0, 100, 0, 50_000);
+        // Lower score, but small enough to fit.
+        let small_tx = signed_tx_with_gas_limit(&small_secret, 0, 10, 0, 10_000);
+        let big_sender = sender_of(&big_tx);
+        let small_sender = sender_of(&small_tx);
+
+        // Balances must cover each tx's cost at its own gas_limit/gas_price,
+        // not a flat multiple of the base gas_limit.
+        let mut pool =
+            pool_with_balances(&[(big_sender, 50_000 * 100), (small_sender, 10_000 * 10)]);
+
+        assert!(pool.import(big_tx).await.unwrap());
+        assert!(pool.import(small_tx).await.unwrap());
+
+        let pending: Vec<U256> = pool
+            .pending(U256::from(20_000))
+            .map(|tx| tx.gas_price)
+            .collect();
+        assert_eq!(pending, vec![U256::from(10)]);
+    }
+
+    #[tokio::test]
+    async fn future_tx_is_promoted_once_its_gap_closes() {
+        let secret = secret_key(10);
+        let tx0 = signed_tx(&secret, 0, 1, 0);
+        let tx1 = signed_tx(&secret, 1, 1, 0);
+        let tx2 = signed_tx(&secret, 2, 1, 0);
+        let sender = sender_of(&tx0);
+
+        let mut pool = pool_with_balance(sender, 21_000 * 10);
+
+        // Both arrive out of order and park in the future queue.
+        assert!(pool.import(tx2.clone()).await.unwrap());
+        assert!(pool.import(tx1.clone()).await.unwrap());
+        assert_eq!(pool.pending(U256::from(1_000_000)).count(), 0);
+
+        // Closing the gap promotes the whole contiguous chain into ready.
+        assert!(pool.import(tx0.clone()).await.unwrap());
+
+        let pending: Vec<U256> = pool.pending(U256::from(1_000_000)).map(|tx| tx.nonce).collect();
+        assert_eq!(
+            pending,
+            vec![U256::from(0), U256::from(1), U256::from(2)]
+        );
+    }
+
+    #[tokio::test]
+    async fn replacement_below_bump_threshold_is_rejected() {
+        let secret = secret_key(11);
+        let tx0 = signed_tx(&secret, 0, 100, 0);
+        let sender = sender_of(&tx0);
+
+        // Balance must cover the highest gas price used below (110), not
+        // just tx0's own cost.
+        let mut pool = pool_with_balance(sender, 21_000 * 150);
+        assert!(pool.import(tx0).await.unwrap());
+
+        // Default bump is 10%; an 9% bump doesn't clear it.
+        let too_small_bump = signed_tx(&secret, 0, 109, 0);
+        assert!(matches!(
+            pool.import(too_small_bump).await,
+            Err(ImportError::FeeTooLow)
+        ));
+
+        // A 10% bump does.
+        let enough_bump = signed_tx(&secret, 0, 110, 0);
+        assert!(pool.import(enough_bump).await.unwrap());
+    }
+
+    #[tokio::test]
+    async fn raising_min_gas_price_sweeps_stale_txs_and_dependents() {
+        let secret = secret_key(12);
+        let tx0 = signed_tx(&secret, 0, 1, 0);
+        let tx1 = signed_tx(&secret, 1, 3, 0);
+        let sender = sender_of(&tx0);
+
+        let mut pool = pool_with_balance(sender, 21_000 * 10);
+
+        let hash0 = RichTransaction::try_from(tx0.clone()).unwrap().hash;
+        let hash1 = RichTransaction::try_from(tx1.clone()).unwrap().hash;
+
+        assert!(pool.import(tx0).await.unwrap());
+        assert!(pool.import(tx1).await.unwrap());
+
+        // Raising the floor above tx0's price sweeps tx0, taking its
+        // nonce-dependent tx1 with it even though tx1 never fell below the
+        // floor itself.
+        pool.set_min_gas_price(U256::from(2));
+
+        assert!(pool.get(hash0).is_none());
+        assert!(pool.get(hash1).is_none());
+
+        // And the floor is enforced on new imports too.
+        let below_floor = signed_tx(&secret, 0, 1, 0);
+        assert!(matches!(
+            pool.import(below_floor).await,
+            Err(ImportError::FeeTooLow)
+        ));
+    }
+
+    #[tokio::test]
+    async fn chain_id_mismatch_is_rejected() {
+        let secret = secret_key(8);
+        let right_chain_tx = signed_tx_eip155(&secret, 0, 1, 0, 1);
+        let sender = sender_of(&right_chain_tx);
+
+        let mut pool = pool_with_balance(sender, 21_000 * 10);
+        pool.set_chain_id(Some(1));
+
+        assert!(pool.import(right_chain_tx).await.unwrap());
+
+        let wrong_chain_tx = signed_tx_eip155(&secret, 1, 1, 0, 2);
+        assert!(matches!(
+            pool.import(wrong_chain_tx).await,
+            Err(ImportError::InvalidTransaction(_))
+        ));
+    }
+
+    #[tokio::test]
+    async fn unprotected_tx_needs_allow_unprotected() {
+        let secret = secret_key(9);
+        let unprotected_tx = signed_tx(&secret, 0, 1, 0);
+        let sender = sender_of(&unprotected_tx);
+
+        let mut pool = pool_with_balance(sender, 21_000 * 10);
+        pool.set_chain_id(Some(1));
+
+        assert!(matches!(
+            pool.import(unprotected_tx.clone()).await,
+            Err(ImportError::InvalidTransaction(_))
+        ));
+
+        pool.set_allow_unprotected(true);
+        assert!(pool.import(unprotected_tx).await.unwrap());
+    }
+}